@@ -0,0 +1,614 @@
+//! Deserialisation: read `RawCbor` and build up your own objects.
+
+use internal::core;
+use std::string::String;
+use std::vec::Vec;
+
+use error::Error;
+use types::Type;
+use len::Len;
+use result::Result;
+
+/// decode an IEEE-754 half precision (binary16) value into the nearest f64,
+/// per [RFC 7049 Appendix D](https://tools.ietf.org/html/rfc7049#appendix-D).
+fn decode_f16(half: u16) -> f64 {
+    let sign     = if (half >> 15) & 0b1 == 1 { -1.0 } else { 1.0 };
+    let exponent = (half >> 10) & 0b1_1111;
+    let mantissa = (half & 0b11_1111_1111) as f64;
+    if exponent == 0 {
+        sign * mantissa * 2f64.powi(-24)
+    } else if exponent == 0b1_1111 {
+        if mantissa == 0.0 { sign * f64::INFINITY } else { f64::NAN }
+    } else {
+        sign * (1.0 + mantissa / 1024.0) * 2f64.powi(exponent as i32 - 15)
+    }
+}
+
+/// fold a big-endian byte string into a `u64`, erroring if it is wider
+/// than 8 bytes.
+fn bytes_to_u64(b: &[u8]) -> Result<u64> {
+    if b.len() > 8 { return Err(Error::CustomError("bignum magnitude does not fit in a u64")); }
+    Ok(b.iter().fold(0u64, |acc, &byte| (acc << 8) | byte as u64))
+}
+
+/// turn the magnitude `n` of a CBOR negative integer (`-1 - n`) into an
+/// `i64`, erroring rather than silently wrapping when `n` is too large to
+/// be represented (i.e. the true value is below `i64::MIN`).
+fn negative_magnitude_to_i64(n: u64) -> Result<i64> {
+    if n > i64::MAX as u64 { return Err(Error::ExpectedI64); }
+    Ok(-1 - n as i64)
+}
+
+/// strip leading zero bytes from a bignum magnitude, keeping at least
+/// one byte.
+fn strip_leading_zeros<'a>(bytes: Bytes<'a>) -> Bytes<'a> {
+    match bytes {
+        Bytes::Borrowed(mut b) => {
+            while b.len() > 1 && b[0] == 0 { b = &b[1..]; }
+            Bytes::Borrowed(b)
+        }
+        Bytes::Owned(mut v) => {
+            if v.is_empty() { return Bytes::Owned(v); }
+            let mut start = 0;
+            while start < v.len() - 1 && v[start] == 0 { start += 1; }
+            if start > 0 { v = v.split_off(start); }
+            Bytes::Owned(v)
+        }
+    }
+}
+
+fn type_of_major(major: u8) -> Type {
+    match major {
+        0 => Type::UnsignedInteger,
+        1 => Type::NegativeInteger,
+        2 => Type::Bytes,
+        3 => Type::Text,
+        4 => Type::Array,
+        5 => Type::Map,
+        6 => Type::Tag,
+        _ => Type::Special,
+    }
+}
+
+/// a CBOR byte string. Definite length strings are read with zero copy,
+/// borrowed from the [`RawCbor`](./struct.RawCbor.html) internal buffer;
+/// indefinite length (chunked) strings have to be concatenated into an
+/// owned buffer instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bytes<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Vec<u8>),
+}
+impl<'a> AsRef<[u8]> for Bytes<'a> {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Bytes::Borrowed(b) => b,
+            Bytes::Owned(v)    => v.as_ref(),
+        }
+    }
+}
+impl<'a> core::ops::Deref for Bytes<'a> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] { self.as_ref() }
+}
+
+/// a CBOR UTF8 text string, see [`Bytes`](./enum.Bytes.html) for the
+/// borrowed/owned distinction between definite and indefinite length strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Text<'a> {
+    Borrowed(&'a str),
+    Owned(String),
+}
+impl<'a> AsRef<str> for Text<'a> {
+    fn as_ref(&self) -> &str {
+        match self {
+            Text::Borrowed(s) => s,
+            Text::Owned(s)    => s.as_str(),
+        }
+    }
+}
+impl<'a> core::ops::Deref for Text<'a> {
+    type Target = str;
+    fn deref(&self) -> &str { self.as_ref() }
+}
+
+/// Trait to help implement a deserialiser for a given type `Self`.
+pub trait Deserialize: Sized {
+    fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self>;
+}
+
+/// `RawCbor` parses a CBOR encoded buffer, one element at a time, without
+/// any intermediate representation. It keeps a reference to the original
+/// buffer so objects that don't need to be copied (see [`Bytes`]) can
+/// borrow directly from it.
+#[derive(Debug, Clone)]
+pub struct RawCbor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    max_len: Option<u64>,
+    max_depth: Option<usize>,
+    depth: usize,
+}
+impl<'a> From<&'a [u8]> for RawCbor<'a> {
+    fn from(buf: &'a [u8]) -> Self {
+        RawCbor { buf, pos: 0, max_len: None, max_depth: None, depth: 0 }
+    }
+}
+impl<'a> RawCbor<'a> {
+    /// number of bytes remaining to parse
+    pub fn remaining(&self) -> usize { self.buf.len() - self.pos }
+
+    /// reject any declared array/map/bytes/text length above `max_len`.
+    /// Guards against a hostile length header (e.g. an 8-byte header
+    /// claiming a multi-gigabyte array) triggering a huge allocation.
+    pub fn with_max_len(mut self, max_len: u64) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+    /// reject array/map/tag nesting deeper than `max_depth`. Guards
+    /// against a deeply nested document blowing the call stack of a
+    /// recursive [`Deserialize`](trait.Deserialize.html) implementation.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    fn check_len(&self, len: Len) -> Result<Len> {
+        if let Len::Len(l) = len {
+            if let Some(max) = self.max_len {
+                if l > max { return Err(Error::LengthLimitExceeded(l, max)); }
+            }
+        }
+        Ok(len)
+    }
+    /// called every time we descend into a new array/map/tag, whether it
+    /// announces a definite or an indefinite length.
+    fn enter_container(&mut self) -> Result<()> {
+        self.depth += 1;
+        if let Some(max) = self.max_depth {
+            if self.depth > max { return Err(Error::DepthLimitExceeded(self.depth, max)); }
+        }
+        Ok(())
+    }
+    /// callers that recurse into the elements of an array/map, or into
+    /// the item wrapped by a tag, must call this once they are done
+    /// reading them so the depth counter stays accurate for their own
+    /// caller. See [`Value`](../value/enum.Value.html) for an example.
+    pub fn leave_container(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    fn peek_u8(&self) -> Result<u8> {
+        self.buf.get(self.pos).cloned().ok_or(Error::NotEnough(self.remaining(), 1))
+    }
+    fn u8(&mut self) -> Result<u8> {
+        let v = self.peek_u8()?;
+        self.pos += 1;
+        Ok(v)
+    }
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.remaining() < len { return Err(Error::NotEnough(self.remaining(), len)); }
+        let s = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(s)
+    }
+    fn be_u16(&mut self) -> Result<u16> {
+        let b = self.take(2)?;
+        Ok(((b[0] as u16) << 8) | (b[1] as u16))
+    }
+    fn be_u32(&mut self) -> Result<u32> {
+        let b = self.take(4)?;
+        Ok(b.iter().fold(0u32, |acc, &byte| (acc << 8) | byte as u32))
+    }
+    fn be_u64(&mut self) -> Result<u64> {
+        let b = self.take(8)?;
+        Ok(b.iter().fold(0u64, |acc, &byte| (acc << 8) | byte as u64))
+    }
+
+    /// read the next major type and additional info byte without consuming it
+    fn peek_header(&self) -> Result<(u8, u8)> {
+        let b = self.peek_u8()?;
+        Ok((b >> 5, b & 0b0001_1111))
+    }
+    /// read and consume the next major type and additional info byte
+    fn cbor_header(&mut self) -> Result<(u8, u8)> {
+        let b = self.u8()?;
+        Ok((b >> 5, b & 0b0001_1111))
+    }
+    /// decode the length/value encoded by the given additional info byte,
+    /// reading any extra bytes it announces
+    fn read_len(&mut self, info: u8) -> Result<Len> {
+        match info {
+            0..=23 => Ok(Len::Len(info as u64)),
+            24 => Ok(Len::Len(self.u8()? as u64)),
+            25 => Ok(Len::Len(self.be_u16()? as u64)),
+            26 => Ok(Len::Len(self.be_u32()? as u64)),
+            27 => Ok(Len::Len(self.be_u64()?)),
+            31 => Ok(Len::Indefinite),
+            _  => Err(Error::UnknownLenType(info)),
+        }
+    }
+    fn expect_major_type(&mut self, expected: u8, ty: Type) -> Result<u8> {
+        let (major, info) = self.cbor_header()?;
+        if major != expected { return Err(Error::Expected(ty, type_of_major(major))); }
+        Ok(info)
+    }
+
+    /// read an unsigned integer (major type 0), transparently accepting
+    /// an unsigned [`bignum`](#method.bignum) (tag 2) that fits in a `u64`.
+    pub fn unsigned_integer(&mut self) -> Result<u64> {
+        if self.peek_header()?.0 == 6 {
+            let (negative, magnitude) = self.bignum()?;
+            if negative { return Err(Error::Expected(Type::UnsignedInteger, Type::NegativeInteger)); }
+            return bytes_to_u64(magnitude.as_ref());
+        }
+        let info = self.expect_major_type(0, Type::UnsignedInteger)?;
+        match self.read_len(info)? {
+            Len::Len(v)    => Ok(v),
+            Len::Indefinite => Err(Error::IndefiniteLenNotSupported(Type::UnsignedInteger)),
+        }
+    }
+    /// read a negative integer (major type 1), returned as the actual
+    /// (negative) value it represents. Transparently accepts a negative
+    /// [`bignum`](#method.bignum) (tag 3) that fits in an `i64`.
+    pub fn negative_integer(&mut self) -> Result<i64> {
+        if self.peek_header()?.0 == 6 {
+            let (negative, magnitude) = self.bignum()?;
+            if !negative { return Err(Error::Expected(Type::NegativeInteger, Type::UnsignedInteger)); }
+            return negative_magnitude_to_i64(bytes_to_u64(magnitude.as_ref())?);
+        }
+        let info = self.expect_major_type(1, Type::NegativeInteger)?;
+        match self.read_len(info)? {
+            Len::Len(v)    => negative_magnitude_to_i64(v),
+            Len::Indefinite => Err(Error::IndefiniteLenNotSupported(Type::NegativeInteger)),
+        }
+    }
+
+    /// read an arbitrary precision integer encoded with the standard CBOR
+    /// bignum tags ([RFC 7049 §2.4.2](https://tools.ietf.org/html/rfc7049#section-2.4.2)):
+    /// tag 2 wraps the big-endian unsigned magnitude, tag 3 wraps the
+    /// magnitude of `-1 - n`. Returns `(negative, magnitude)` with any
+    /// leading zero bytes stripped from the magnitude.
+    pub fn bignum(&mut self) -> Result<(bool, Bytes<'a>)> {
+        let tag = self.raw_tag()?;
+        let negative = match tag {
+            2 => false,
+            3 => true,
+            _ => return Err(Error::CustomError("expected cbor bignum tag (2 or 3)")),
+        };
+        let bytes = self.bytes()?;
+        self.leave_container();
+        Ok((negative, strip_leading_zeros(bytes)))
+    }
+
+    /// read a byte string (major type 2), definite or indefinite length.
+    /// An indefinite length string is the concatenation of its definite
+    /// length chunks, all of which must be byte strings themselves.
+    pub fn bytes(&mut self) -> Result<Bytes<'a>> {
+        let info = self.expect_major_type(2, Type::Bytes)?;
+        let len = self.read_len(info)?;
+        match self.check_len(len)? {
+            Len::Len(len)   => Ok(Bytes::Borrowed(self.take(len as usize)?)),
+            Len::Indefinite => Ok(Bytes::Owned(self.read_indefinite_chunks(2, Type::Bytes)?)),
+        }
+    }
+    /// read a UTF8 text string (major type 3), definite or indefinite
+    /// length, see [`bytes`](#method.bytes). UTF-8 validity is checked
+    /// once on the fully concatenated result.
+    pub fn text(&mut self) -> Result<Text<'a>> {
+        let info = self.expect_major_type(3, Type::Text)?;
+        let len = self.read_len(info)?;
+        match self.check_len(len)? {
+            Len::Len(len) => {
+                Ok(Text::Borrowed(core::str::from_utf8(self.take(len as usize)?)?))
+            }
+            Len::Indefinite => {
+                let bytes = self.read_indefinite_chunks(3, Type::Text)?;
+                let text = String::from_utf8(bytes).map_err(|e| Error::InvalidTextError(e.utf8_error()))?;
+                Ok(Text::Owned(text))
+            }
+        }
+    }
+
+    /// read the definite-length chunks of an indefinite length byte/text
+    /// string (the leading header has already been consumed), concatenating
+    /// their payloads. Every chunk must be a definite-length string of the
+    /// given major type; nested indefinite length chunks are forbidden.
+    fn read_indefinite_chunks(&mut self, major: u8, ty: Type) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        loop {
+            if self.is_break()? {
+                self.special_break()?;
+                break;
+            }
+            let info = self.expect_major_type(major, ty)?;
+            let len = self.read_len(info)?;
+            match self.check_len(len)? {
+                Len::Len(len)   => out.extend_from_slice(self.take(len as usize)?),
+                Len::Indefinite => return Err(Error::IndefiniteLenNotSupported(ty)),
+            }
+        }
+        Ok(out)
+    }
+
+    /// read the length of an array (major type 4); the caller is then
+    /// responsible for reading exactly that many elements (or, for an
+    /// indefinite length array, elements until [`special_break`] succeeds)
+    pub fn array(&mut self) -> Result<Len> {
+        let info = self.expect_major_type(4, Type::Array)?;
+        self.enter_container()?;
+        let len = self.read_len(info)?;
+        self.check_len(len)
+    }
+    /// read the length of a map (major type 5), see [`array`](#method.array)
+    pub fn map(&mut self) -> Result<Len> {
+        let info = self.expect_major_type(5, Type::Map)?;
+        self.enter_container()?;
+        let len = self.read_len(info)?;
+        self.check_len(len)
+    }
+
+    /// read a boolean special value (major type 7, additional info 20/21)
+    pub fn bool(&mut self) -> Result<bool> {
+        let info = self.expect_major_type(7, Type::Special)?;
+        match info {
+            20 => Ok(false),
+            21 => Ok(true),
+            _  => Err(Error::ExpectedBool),
+        }
+    }
+    /// read the `null` special value (major type 7, additional info 22)
+    pub fn null(&mut self) -> Result<()> {
+        let info = self.expect_major_type(7, Type::Special)?;
+        match info { 22 => Ok(()), _ => Err(Error::ExpectedNull) }
+    }
+    /// read the `undefined` special value (major type 7, additional info 23)
+    pub fn undefined(&mut self) -> Result<()> {
+        let info = self.expect_major_type(7, Type::Special)?;
+        match info { 23 => Ok(()), _ => Err(Error::ExpectedUndefined) }
+    }
+    /// read the `break` marker (major type 7, additional info 31) that
+    /// terminates an indefinite length array/map
+    pub fn special_break(&mut self) -> Result<()> {
+        let info = self.expect_major_type(7, Type::Special)?;
+        match info { 31 => Ok(()), _ => Err(Error::ExpectedBreak) }
+    }
+    /// read a floating point special value (major type 7, additional info
+    /// 25/26/27: half, single or double precision), widened to `f64`.
+    pub fn float(&mut self) -> Result<f64> {
+        let info = self.expect_major_type(7, Type::Special)?;
+        match info {
+            25 => Ok(decode_f16(self.be_u16()?)),
+            26 => Ok(f32::from_bits(self.be_u32()?) as f64),
+            27 => Ok(f64::from_bits(self.be_u64()?)),
+            _  => Err(Error::ExpectedFloat),
+        }
+    }
+    /// read a floating point special value, same as [`float`](#method.float).
+    pub fn f64(&mut self) -> Result<f64> { self.float() }
+    /// read a floating point special value encoded as half or single
+    /// precision (additional info 25/26), narrowed to `f32`.
+    pub fn f32(&mut self) -> Result<f32> {
+        let info = self.expect_major_type(7, Type::Special)?;
+        match info {
+            25 => Ok(decode_f16(self.be_u16()?) as f32),
+            26 => Ok(f32::from_bits(self.be_u32()?)),
+            _  => Err(Error::ExpectedFloat),
+        }
+    }
+
+    /// `true` if the next byte to read is the `break` marker, without
+    /// consuming anything; useful to detect the end of an indefinite
+    /// length array/map
+    pub fn is_break(&self) -> Result<bool> {
+        let (major, info) = self.peek_header()?;
+        Ok(major == 7 && info == 31)
+    }
+
+    /// peek at the [`Type`] of the next item to read, without consuming
+    /// anything. Useful for generic decoders (e.g. [`Value`](../value/enum.Value.html))
+    /// that need to dispatch on the shape of the data ahead.
+    pub fn cbor_type(&self) -> Result<Type> {
+        let (major, _) = self.peek_header()?;
+        Ok(type_of_major(major))
+    }
+
+    /// peek at the additional info nibble of the next special (major type 7)
+    /// value, without consuming it. Used internally to dispatch between the
+    /// different kinds of specials (bool, null, undefined, ...).
+    pub(crate) fn peek_special_info(&self) -> Result<u8> {
+        let (_, info) = self.peek_header()?;
+        Ok(info)
+    }
+
+    /// read a tag (major type 6) and return the tag number, leaving the
+    /// tagged item to be read next with whatever call reads it (e.g.
+    /// [`unsigned_integer`](#method.unsigned_integer), `Deserialize::deserialize`, ...).
+    /// Counts towards the configured [`max_depth`](#method.with_max_depth);
+    /// call [`leave_container`](#method.leave_container) once the tagged
+    /// item has been read.
+    pub fn tag(&mut self) -> Result<u64> {
+        self.raw_tag()
+    }
+    /// read a tag, assert it is `expected`, then delegate to `f` to read
+    /// the tagged item.
+    pub fn with_tag<T, F>(&mut self, expected: u64, f: F) -> Result<T>
+        where F: FnOnce(&mut Self) -> Result<T>
+    {
+        let tag = self.tag()?;
+        if tag != expected {
+            self.leave_container();
+            return Err(Error::UnexpectedTag(expected, tag));
+        }
+        let result = f(self);
+        self.leave_container();
+        result
+    }
+
+    fn raw_tag(&mut self) -> Result<u64> {
+        let info = self.expect_major_type(6, Type::Tag)?;
+        self.enter_container()?;
+        match self.read_len(info)? {
+            Len::Len(v)     => Ok(v),
+            Len::Indefinite => Err(Error::IndefiniteLenNotSupported(Type::Tag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use se;
+
+    #[test]
+    fn decode_half_precision_rfc_examples() {
+        // RFC 7049 Appendix A examples.
+        assert_eq!(RawCbor::from(&[0xf9, 0x3e, 0x00][..]).float().unwrap(), 1.5);
+        assert_eq!(RawCbor::from(&[0xf9, 0x00, 0x00][..]).float().unwrap(), 0.0);
+        assert!(RawCbor::from(&[0xf9, 0x7e, 0x00][..]).float().unwrap().is_nan());
+        assert_eq!(RawCbor::from(&[0xf9, 0x7c, 0x00][..]).float().unwrap(), f64::INFINITY);
+    }
+
+    #[test]
+    fn decode_single_and_double_precision() {
+        let bits = 1.0e10f32.to_bits();
+        let mut single = vec![0xfa];
+        single.extend_from_slice(&bits.to_be_bytes());
+        assert_eq!(RawCbor::from(&single[..]).float().unwrap(), 1.0e10f64);
+
+        let bits = 1.1f64.to_bits();
+        let mut double = vec![0xfb];
+        double.extend_from_slice(&bits.to_be_bytes());
+        assert_eq!(RawCbor::from(&double[..]).float().unwrap(), 1.1);
+    }
+
+    #[test]
+    fn indefinite_bytes_concatenates_chunks() {
+        // (_ h'0102', h'030405')
+        let input = [0x5f, 0x42, 0x01, 0x02, 0x43, 0x03, 0x04, 0x05, 0xff];
+        let bytes = RawCbor::from(&input[..]).bytes().unwrap();
+        assert_eq!(bytes.as_ref(), [1, 2, 3, 4, 5].as_ref());
+    }
+
+    #[test]
+    fn indefinite_text_concatenates_chunks_and_checks_utf8_on_result() {
+        // (_ "a", "b")
+        let input = [0x7f, 0x61, 0x61, 0x61, 0x62, 0xff];
+        let text = RawCbor::from(&input[..]).text().unwrap();
+        assert_eq!(text.as_ref(), "ab");
+
+        // (_ "\xc3", "\xa9") — "é" split across two chunks, neither of
+        // which is valid UTF-8 on its own
+        let input = [0x7f, 0x61, 0xc3, 0x61, 0xa9, 0xff];
+        let text = RawCbor::from(&input[..]).text().unwrap();
+        assert_eq!(text.as_ref(), "\u{e9}");
+    }
+
+    #[test]
+    fn indefinite_bytes_rejects_mismatched_chunk_type() {
+        // (_ h'0102', "a") — a text chunk inside an indefinite byte string
+        let input = [0x5f, 0x42, 0x01, 0x02, 0x61, 0x61, 0xff];
+        assert!(RawCbor::from(&input[..]).bytes().is_err());
+    }
+
+    #[test]
+    fn indefinite_bytes_rejects_nested_indefinite_chunk() {
+        let input = [0x5f, 0x5f, 0x41, 0x01, 0xff, 0xff];
+        assert!(RawCbor::from(&input[..]).bytes().is_err());
+    }
+
+    #[test]
+    fn length_limit_rejects_oversized_declared_length() {
+        // array header declaring a 4-byte length of 65536
+        let input = [0x9a, 0x00, 0x01, 0x00, 0x00];
+        let mut raw = RawCbor::from(&input[..]).with_max_len(100);
+        match raw.array() {
+            Err(Error::LengthLimitExceeded(65536, 100)) => {}
+            other => panic!("expected LengthLimitExceeded, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn depth_limit_rejects_deep_nesting() {
+        // [[[]]]
+        let input = [0x81, 0x81, 0x80];
+        let mut raw = RawCbor::from(&input[..]).with_max_depth(2);
+        assert!(raw.array().is_ok());
+        assert!(raw.array().is_ok());
+        match raw.array() {
+            Err(Error::DepthLimitExceeded(3, 2)) => {}
+            other => panic!("expected DepthLimitExceeded, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn depth_limit_counts_indefinite_containers() {
+        // (_ (_ ))
+        let input = [0x9f, 0x9f, 0xff, 0xff];
+        let mut raw = RawCbor::from(&input[..]).with_max_depth(1);
+        assert!(raw.array().is_ok());
+        assert!(raw.array().is_err());
+    }
+
+    #[test]
+    fn bignum_strips_leading_zero_bytes() {
+        // tag 2, byte string 0x00 0x01
+        let input = [0xc2, 0x42, 0x00, 0x01];
+        let (negative, magnitude) = RawCbor::from(&input[..]).bignum().unwrap();
+        assert!(!negative);
+        assert_eq!(magnitude.as_ref(), [0x01].as_ref());
+    }
+
+    #[test]
+    fn write_bignum_round_trips_through_bignum() {
+        let magnitude = [0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let bytes = se::Serializer::new_vec().write_bignum(&magnitude, true).unwrap().finalize();
+        let (negative, decoded) = RawCbor::from(&bytes[..]).bignum().unwrap();
+        assert!(negative);
+        assert_eq!(decoded.as_ref(), magnitude.as_ref());
+    }
+
+    #[test]
+    fn unsigned_integer_accepts_unsigned_bignum() {
+        let magnitude = u64::MAX.to_be_bytes();
+        let bytes = se::Serializer::new_vec().write_bignum(&magnitude, false).unwrap().finalize();
+        assert_eq!(RawCbor::from(&bytes[..]).unsigned_integer().unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn negative_integer_accepts_negative_bignum_within_range() {
+        // magnitude = i64::MAX as u64, representing -1 - i64::MAX = i64::MIN
+        let magnitude = (i64::MAX as u64).to_be_bytes();
+        let bytes = se::Serializer::new_vec().write_bignum(&magnitude, true).unwrap().finalize();
+        assert_eq!(RawCbor::from(&bytes[..]).negative_integer().unwrap(), i64::MIN);
+    }
+
+    #[test]
+    fn negative_integer_errors_rather_than_wraps_when_bignum_too_large_for_i64() {
+        // magnitude = i64::MAX + 1, too large to represent as -1 - n in an i64
+        let magnitude = (i64::MAX as u64 + 1).to_be_bytes();
+        let bytes = se::Serializer::new_vec().write_bignum(&magnitude, true).unwrap().finalize();
+        match RawCbor::from(&bytes[..]).negative_integer() {
+            Err(Error::ExpectedI64) => {}
+            other => panic!("expected ExpectedI64, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tag_round_trips_with_write_tag() {
+        let bytes = se::Serializer::new_vec().write_tag(42).unwrap().write_unsigned_integer(1).unwrap().finalize();
+        let mut raw = RawCbor::from(&bytes[..]);
+        assert_eq!(raw.tag().unwrap(), 42);
+        assert_eq!(raw.unsigned_integer().unwrap(), 1);
+    }
+
+    #[test]
+    fn with_tag_rejects_unexpected_tag_number() {
+        let bytes = se::Serializer::new_vec().write_tag(42).unwrap().write_unsigned_integer(1).unwrap().finalize();
+        let mut raw = RawCbor::from(&bytes[..]);
+        match raw.with_tag(7, |raw| raw.unsigned_integer()) {
+            Err(Error::UnexpectedTag(7, 42)) => {}
+            other => panic!("expected UnexpectedTag, got {:?}", other),
+        }
+    }
+}
@@ -32,6 +32,22 @@ pub enum Error {
     UnknownLenType(u8),
     IndefiniteLenNotSupported(Type),
 
+    /// a declared array/map/bytes/text length was above the bound set with
+    /// [`RawCbor::with_max_len`](../de/struct.RawCbor.html#method.with_max_len).
+    /// the first element is the declared length, the second is the
+    /// configured maximum.
+    LengthLimitExceeded(u64, u64),
+    /// nesting (array/map/tag) went deeper than the bound set with
+    /// [`RawCbor::with_max_depth`](../de/struct.RawCbor.html#method.with_max_depth).
+    /// the first element is the attempted depth, the second is the
+    /// configured maximum.
+    DepthLimitExceeded(usize, usize),
+
+    /// [`RawCbor::with_tag`](../de/struct.RawCbor.html#method.with_tag) read
+    /// a tag number different from the one it was asserting. The first
+    /// element is the expected tag, the second is the tag actually read.
+    UnexpectedTag(u64, u64),
+
     InvalidTextError(core::str::Utf8Error),
     WriteError(super::internal::WriteError),
 
@@ -44,7 +60,7 @@ impl From<super::internal::WriteError> for Error {
     fn from(e: super::internal::WriteError) -> Self { Error::WriteError(e) }
 }
 
-impl<'a> core::fmt::Display for Error {
+impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         use Error::*;
         match self {
@@ -66,6 +82,9 @@ impl<'a> core::fmt::Display for Error {
             UnsupportedKeyType(t) => write!(f, "Invalid cbor: unsupported object key type `{:?}'.", t),
             Expected(exp, got) => write!(f, "Invalid cbor: not the right type, expected `{:?}' byte received `{:?}'.", exp, got),
             IndefiniteLenNotSupported(t) => write!(f, "Invalid cbor: indefinite length not supported for cbor object of type `{:?}'.", t),
+            LengthLimitExceeded(len, max) => write!(f, "Invalid cbor: declared length {} is above the configured maximum of {}.", len, max),
+            DepthLimitExceeded(depth, max) => write!(f, "Invalid cbor: nesting depth {} is above the configured maximum of {}.", depth, max),
+            UnexpectedTag(exp, got) => write!(f, "Invalid cbor: expected tag {} but received tag {}.", exp, got),
             UnknownLenType(byte) => write!(f, "Invalid cbor: not the right sub type: 0b{:05b}", byte),
             InvalidTextError(utf8_error) => write!(f, "Invalid cbor: expected a valid utf8 string text. {:?}", utf8_error),
             WriteError(write_error) => write!(f, "Invalid cbor: write error: {:?}.", write_error),
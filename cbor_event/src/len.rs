@@ -0,0 +1,20 @@
+/// the length of a CBOR array, map, byte string or text string.
+///
+/// CBOR allows array and map (and, with the `0x1f` additional info byte,
+/// byte/text strings too) to be encoded without announcing their length
+/// up front, relying instead on a terminating break (`0xFF`). [`Len`]
+/// captures both cases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Len {
+    Len(u64),
+    Indefinite,
+}
+impl Len {
+    /// `true` if this is the indefinite length marker.
+    pub fn indefinite(&self) -> bool {
+        match self {
+            Len::Indefinite => true,
+            Len::Len(_) => false,
+        }
+    }
+}
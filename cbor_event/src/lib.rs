@@ -15,10 +15,10 @@
 //! Here is the list of supported CBOR primary [`Type`]:
 //!
 //! - Unsigned and Negative Integers;
-//! - Bytes and UTF8 String (**finite length only**);
+//! - Bytes and UTF8 String (of finite and indefinite, chunked, size);
 //! - Array and Map (of finite and indefinite size);
 //! - Tag;
-//! - Specials (`bool`, `null`... **except floating points**).
+//! - Specials (`bool`, `null`, floating points (half, single, double precision), ...).
 //!
 //! ## Raw deserialisation: [`RawCbor`]
 //!
@@ -87,7 +87,7 @@ const CBOR_PAYLOAD_LENGTH_U64 : u8 = 27;
 pub fn test_encode_decode<V: Sized+PartialEq+Serialize+Deserialize>(v: &V) -> Result<bool> {
     let bytes = Serialize::serialize(v, se::Serializer::new_vec())?.finalize();
 
-    let mut raw = de::RawCbor::from(&bytes);
+    let mut raw = de::RawCbor::from(&bytes[..]);
     let v_ = Deserialize::deserialize(&mut raw)?;
 
     Ok(v == &v_)
@@ -160,7 +160,7 @@ mod internal {
     impl<'a> AsRef<[u8]> for RefBuffer<'a> {
         fn as_ref(&self) -> &[u8] { &self.buffer[..self.offset] }
     }
-    impl<'a, 'b> PartialEq<[u8]> for RefBuffer<'a> {
+    impl<'a> PartialEq<[u8]> for RefBuffer<'a> {
         fn eq(&self, lhs: &[u8]) -> bool { self.buffer[..self.offset] == lhs[..] }
     }
     impl<'a, 'b> PartialEq<&'b [u8]> for RefBuffer<'a> {
@@ -0,0 +1,83 @@
+use de::{Deserialize, RawCbor};
+use se::{Serialize, Serializer};
+use error::Error;
+use result::Result;
+
+macro_rules! auto_serialize_unsigned_integer {
+    ($ty:ty) => {
+        impl Serialize for $ty {
+            fn serialize(&self, serializer: Serializer) -> Result<Serializer> {
+                serializer.write_unsigned_integer(*self as u64)
+            }
+        }
+        impl Deserialize for $ty {
+            fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self> {
+                let v = raw.unsigned_integer()?;
+                if v > <$ty>::MAX as u64 {
+                    return Err(Error::CustomError(concat!("value out of range for ", stringify!($ty))));
+                }
+                Ok(v as $ty)
+            }
+        }
+    }
+}
+auto_serialize_unsigned_integer!(u8);
+auto_serialize_unsigned_integer!(u16);
+auto_serialize_unsigned_integer!(u32);
+
+impl Serialize for u64 {
+    fn serialize(&self, serializer: Serializer) -> Result<Serializer> {
+        serializer.write_unsigned_integer(*self)
+    }
+}
+impl Deserialize for u64 {
+    fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self> {
+        raw.unsigned_integer()
+    }
+}
+
+impl Serialize for f32 {
+    fn serialize(&self, serializer: Serializer) -> Result<Serializer> {
+        serializer.write_f32(*self)
+    }
+}
+impl Deserialize for f32 {
+    fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self> {
+        raw.f32()
+    }
+}
+
+impl Serialize for f64 {
+    fn serialize(&self, serializer: Serializer) -> Result<Serializer> {
+        serializer.write_f64(*self)
+    }
+}
+impl Deserialize for f64 {
+    fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self> {
+        raw.f64()
+    }
+}
+
+impl Serialize for bool {
+    fn serialize(&self, serializer: Serializer) -> Result<Serializer> {
+        serializer.write_bool(*self)
+    }
+}
+impl Deserialize for bool {
+    fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self> {
+        raw.bool()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Serialize for String {
+    fn serialize(&self, serializer: Serializer) -> Result<Serializer> {
+        serializer.write_text(self.as_str())
+    }
+}
+#[cfg(feature = "std")]
+impl Deserialize for String {
+    fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self> {
+        Ok(raw.text()?.as_ref().to_string())
+    }
+}
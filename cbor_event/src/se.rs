@@ -0,0 +1,335 @@
+//! Serialisation: build up a CBOR encoded buffer from your own objects.
+
+use len::Len;
+use result::Result;
+use super::{Write, MAX_INLINE_ENCODING, CBOR_PAYLOAD_LENGTH_U8, CBOR_PAYLOAD_LENGTH_U16, CBOR_PAYLOAD_LENGTH_U32, CBOR_PAYLOAD_LENGTH_U64};
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// try to encode `v` as an IEEE-754 half precision (binary16) value,
+/// returning `None` when the conversion would not round-trip exactly.
+fn encode_f16(v: f32) -> Option<u16> {
+    if v.is_nan() { return Some(0b0111_1100_0000_0001); }
+    let bits = v.to_bits();
+    let sign = ((bits >> 31) & 1) as u16;
+    if v == 0.0 { return Some(sign << 15); }
+    if v.is_infinite() { return Some((sign << 15) | 0b0111_1100_0000_0000); }
+    let exponent = ((bits >> 23) & 0x00ff) as i32 - 127;
+    let mantissa = bits & 0x007f_ffff;
+    let half_exponent = exponent + 15;
+    if half_exponent >= 0b1_1111 { return None; }
+    if half_exponent <= 0 {
+        // half precision subnormal: v = m * 2^-24 with m < 1024
+        let shift = -(exponent + 1);
+        if shift >= 24 { return None; }
+        let significand = mantissa | 0x0080_0000;
+        if significand & ((1 << shift) - 1) != 0 { return None; }
+        return Some((sign << 15) | (significand >> shift) as u16);
+    }
+    if mantissa & 0x0000_1fff != 0 { return None; }
+    let half_mantissa = (mantissa >> 13) as u16;
+    Some((sign << 15) | ((half_exponent as u16) << 10) | half_mantissa)
+}
+
+/// Trait to help implement a serialiser for a given type `Self`.
+pub trait Serialize {
+    fn serialize(&self, serializer: Serializer) -> Result<Serializer>;
+}
+
+/// A simple, append-only, CBOR encoder. Every `write_*` method consumes
+/// the `Serializer` and returns it, so calls can be chained:
+///
+/// ```
+/// use cbor_event::se::Serializer;
+///
+/// let bytes = Serializer::new_vec()
+///     .write_array(cbor_event::Len::Len(2)).unwrap()
+///     .write_unsigned_integer(1).unwrap()
+///     .write_unsigned_integer(2).unwrap()
+///     .finalize();
+/// assert_eq!(bytes, vec![0x82, 0x01, 0x02]);
+/// ```
+pub struct Serializer<W: Write = Vec<u8>> {
+    writer: W,
+}
+#[cfg(feature = "std")]
+impl Serializer<Vec<u8>> {
+    /// create a new serialiser backed by a growable in-memory buffer
+    pub fn new_vec() -> Self { Serializer { writer: Vec::new() } }
+}
+impl<W: Write> Serializer<W> {
+    /// create a new serialiser writing into the given [`Write`](../trait.Write.html)
+    pub fn new(writer: W) -> Self { Serializer { writer } }
+
+    /// consume the serialiser, returning the underlying writer
+    pub fn finalize(self) -> W { self.writer }
+
+    fn write_byte(mut self, byte: u8) -> Result<Self> {
+        self.writer.write_all(&[byte])?;
+        Ok(self)
+    }
+    fn write_bytes_raw(mut self, bytes: &[u8]) -> Result<Self> {
+        self.writer.write_all(bytes)?;
+        Ok(self)
+    }
+
+    /// emit a major type header, choosing the shortest of the inline/u8/
+    /// u16/u32/u64 length encodings that can represent `value`.
+    fn write_header(self, major: u8, value: u64) -> Result<Self> {
+        let prefix = major << 5;
+        if value <= MAX_INLINE_ENCODING {
+            self.write_byte(prefix | value as u8)
+        } else if value <= u8::MAX as u64 {
+            self.write_byte(prefix | CBOR_PAYLOAD_LENGTH_U8)?
+                .write_bytes_raw(&[value as u8])
+        } else if value <= u16::MAX as u64 {
+            let v = value as u16;
+            self.write_byte(prefix | CBOR_PAYLOAD_LENGTH_U16)?
+                .write_bytes_raw(&[(v >> 8) as u8, v as u8])
+        } else if value <= u32::MAX as u64 {
+            let v = value as u32;
+            self.write_byte(prefix | CBOR_PAYLOAD_LENGTH_U32)?
+                .write_bytes_raw(&[(v >> 24) as u8, (v >> 16) as u8, (v >> 8) as u8, v as u8])
+        } else {
+            self.write_byte(prefix | CBOR_PAYLOAD_LENGTH_U64)?
+                .write_bytes_raw(&[
+                    (value >> 56) as u8, (value >> 48) as u8, (value >> 40) as u8, (value >> 32) as u8,
+                    (value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8,
+                ])
+        }
+    }
+    fn write_indefinite_header(self, major: u8) -> Result<Self> {
+        self.write_byte((major << 5) | 31)
+    }
+    fn write_tag_header(self, tag: u64) -> Result<Self> {
+        self.write_header(6, tag)
+    }
+    fn write_special(self, additional_info: u8) -> Result<Self> {
+        self.write_byte((7 << 5) | additional_info)
+    }
+
+    /// write an unsigned integer (major type 0)
+    pub fn write_unsigned_integer(self, value: u64) -> Result<Self> {
+        self.write_header(0, value)
+    }
+    /// write a negative integer (major type 1); `value` is the actual
+    /// (negative) value to encode
+    pub fn write_negative_integer(self, value: i64) -> Result<Self> {
+        self.write_header(1, (-1 - value) as u64)
+    }
+    /// write a byte string (major type 2)
+    pub fn write_bytes(self, bytes: &[u8]) -> Result<Self> {
+        self.write_header(2, bytes.len() as u64)?.write_bytes_raw(bytes)
+    }
+    /// write a UTF8 text string (major type 3)
+    pub fn write_text(self, text: &str) -> Result<Self> {
+        self.write_header(3, text.len() as u64)?.write_bytes_raw(text.as_bytes())
+    }
+    /// write an array length header (major type 4); `Len::Indefinite`
+    /// must later be closed with [`write_special_break`](#method.write_special_break)
+    pub fn write_array(self, len: Len) -> Result<Self> {
+        match len {
+            Len::Len(l)     => self.write_header(4, l),
+            Len::Indefinite => self.write_indefinite_header(4),
+        }
+    }
+    /// write a map length header (major type 5), see [`write_array`](#method.write_array)
+    pub fn write_map(self, len: Len) -> Result<Self> {
+        match len {
+            Len::Len(l)     => self.write_header(5, l),
+            Len::Indefinite => self.write_indefinite_header(5),
+        }
+    }
+    /// write a boolean special value (major type 7)
+    pub fn write_bool(self, b: bool) -> Result<Self> {
+        self.write_special(if b { 21 } else { 20 })
+    }
+    /// write the `null` special value (major type 7)
+    pub fn write_null(self) -> Result<Self> {
+        self.write_special(22)
+    }
+    /// write the `undefined` special value (major type 7)
+    pub fn write_undefined(self) -> Result<Self> {
+        self.write_special(23)
+    }
+    /// write the `break` marker (major type 7) closing an indefinite
+    /// length array or map
+    pub fn write_special_break(self) -> Result<Self> {
+        self.write_byte(0xFF)
+    }
+
+    fn write_half_bits(self, bits: u16) -> Result<Self> {
+        self.write_byte((7 << 5) | 25)?
+            .write_bytes_raw(&[(bits >> 8) as u8, bits as u8])
+    }
+    /// write `v` as a fixed-width single precision (binary32) float
+    /// (major type 7, additional info 26), regardless of whether a
+    /// narrower encoding would round-trip.
+    pub fn write_f32_exact(self, v: f32) -> Result<Self> {
+        let bits = v.to_bits();
+        self.write_byte((7 << 5) | 26)?
+            .write_bytes_raw(&[(bits >> 24) as u8, (bits >> 16) as u8, (bits >> 8) as u8, bits as u8])
+    }
+    /// write `v` as a fixed-width double precision (binary64) float
+    /// (major type 7, additional info 27), regardless of whether a
+    /// narrower encoding would round-trip.
+    pub fn write_f64_exact(self, v: f64) -> Result<Self> {
+        let bits = v.to_bits();
+        self.write_byte((7 << 5) | 27)?
+            .write_bytes_raw(&[
+                (bits >> 56) as u8, (bits >> 48) as u8, (bits >> 40) as u8, (bits >> 32) as u8,
+                (bits >> 24) as u8, (bits >> 16) as u8, (bits >> 8) as u8, bits as u8,
+            ])
+    }
+    /// write `v` as the narrowest of half/single precision that round-trips
+    /// it exactly, preferring half precision.
+    pub fn write_f32(self, v: f32) -> Result<Self> {
+        match encode_f16(v) {
+            Some(bits) => self.write_half_bits(bits),
+            None       => self.write_f32_exact(v),
+        }
+    }
+    /// write `v` as the narrowest of half/single/double precision that
+    /// round-trips it exactly, preferring half, then single, precision.
+    pub fn write_f64(self, v: f64) -> Result<Self> {
+        if (v as f32) as f64 == v {
+            self.write_f32(v as f32)
+        } else {
+            self.write_f64_exact(v)
+        }
+    }
+
+    /// write a tag (major type 6); the tagged item itself must be
+    /// written immediately after, using the shortest length encoding for
+    /// the tag number.
+    pub fn write_tag(self, tag: u64) -> Result<Self> {
+        self.write_tag_header(tag)
+    }
+
+    /// write an arbitrary precision integer using the standard CBOR
+    /// bignum tags ([RFC 7049 §2.4.2](https://tools.ietf.org/html/rfc7049#section-2.4.2)):
+    /// tag 2 (unsigned) or tag 3 (negative, meaning `-1 - magnitude`)
+    /// followed by the minimal-length big-endian `magnitude` byte string.
+    pub fn write_bignum(self, magnitude: &[u8], negative: bool) -> Result<Self> {
+        let mut magnitude = magnitude;
+        while magnitude.len() > 1 && magnitude[0] == 0 { magnitude = &magnitude[1..]; }
+        let tag = if negative { 3 } else { 2 };
+        self.write_tag_header(tag)?.write_bytes(magnitude)
+    }
+}
+
+/// serialise a known-size collection as a CBOR definite-length array
+pub fn serialize_fixed_array<'a, T: 'a + Serialize, I>(values: I, serializer: Serializer) -> Result<Serializer>
+    where I: ExactSizeIterator<Item = &'a T>
+{
+    let mut serializer = serializer.write_array(Len::Len(values.len() as u64))?;
+    for value in values {
+        serializer = value.serialize(serializer)?;
+    }
+    Ok(serializer)
+}
+
+/// serialise a collection of unknown size as a CBOR indefinite-length array
+pub fn serialize_indefinite_array<'a, T: 'a + Serialize, I>(values: I, serializer: Serializer) -> Result<Serializer>
+    where I: Iterator<Item = &'a T>
+{
+    let mut serializer = serializer.write_array(Len::Indefinite)?;
+    for value in values {
+        serializer = value.serialize(serializer)?;
+    }
+    serializer.write_special_break()
+}
+
+/// serialise a map in [RFC 7049 §3.9](https://tools.ietf.org/html/rfc7049#section-3.9)
+/// canonical form: entries are ordered by the byte-wise lexicographic
+/// ordering of their fully encoded keys, rather than in iteration order.
+///
+/// Every integer and length prefix written by [`Serializer`] already uses
+/// the shortest of the inline/`U8`/`U16`/`U32`/`U64` encodings, so the
+/// only extra work needed for canonical form is this key ordering: each
+/// key/value pair is first serialised into its own buffer (the `new_vec`
+/// path), the pairs are sorted by their encoded key bytes, then written
+/// out in that order.
+#[cfg(feature = "std")]
+pub fn serialize_map_canonical<'a, K: 'a + Serialize, V: 'a + Serialize, I>(pairs: I, serializer: Serializer) -> Result<Serializer>
+    where I: ExactSizeIterator<Item = (&'a K, &'a V)>
+{
+    let mut encoded = Vec::with_capacity(pairs.len());
+    for (k, v) in pairs {
+        let key = k.serialize(Serializer::new_vec())?.finalize();
+        let value = v.serialize(Serializer::new_vec())?.finalize();
+        encoded.push((key, value));
+    }
+    encoded.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut serializer = serializer.write_map(Len::Len(encoded.len() as u64))?;
+    for (key, value) in encoded {
+        serializer = serializer.write_bytes_raw(&key)?.write_bytes_raw(&value)?;
+    }
+    Ok(serializer)
+}
+
+/// encode `bytes` as a CBOR byte string, then embed that encoding itself
+/// as a second CBOR byte string, so that decoding it back requires a
+/// second pass of CBOR decoding (hence "cbor in cbor"). This is how
+/// [`HDAddressPayload`] embeds its (encrypted) derivation path.
+///
+/// [`HDAddressPayload`]: ../../wallet_crypto/hdpayload/struct.HDAddressPayload.html
+#[cfg(feature = "std")]
+pub fn serialize_cbor_in_cbor(bytes: &[u8], serializer: Serializer) -> Result<Serializer> {
+    let inner = Serializer::new_vec().write_bytes(bytes)?.finalize();
+    serializer.write_bytes(&inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use de::RawCbor;
+
+    #[test]
+    fn write_f32_prefers_half_precision() {
+        let bytes = Serializer::new_vec().write_f32(1.5).unwrap().finalize();
+        assert_eq!(bytes, vec![0xf9, 0x3e, 0x00]);
+        assert_eq!(RawCbor::from(&bytes[..]).f32().unwrap(), 1.5);
+    }
+
+    #[test]
+    fn write_f32_falls_back_to_single_precision() {
+        // 1e10 does not fit exactly in a half-precision float.
+        let v = 1e10f32;
+        let bytes = Serializer::new_vec().write_f32(v).unwrap().finalize();
+        assert_eq!(bytes[0], (7 << 5) | 26);
+        assert_eq!(RawCbor::from(&bytes[..]).f32().unwrap(), v);
+    }
+
+    #[test]
+    fn write_f64_falls_back_to_double_precision() {
+        let v = 0.1f64;
+        let bytes = Serializer::new_vec().write_f64(v).unwrap().finalize();
+        assert_eq!(bytes[0], (7 << 5) | 27);
+        assert_eq!(RawCbor::from(&bytes[..]).f64().unwrap(), v);
+    }
+
+    #[test]
+    fn canonical_map_orders_by_encoded_key_bytes_not_insertion_order() {
+        // inserted as "b", "aa", "a"; the shortest key ("a", 2 encoded
+        // bytes) sorts before "b" (also 2 bytes, greater 2nd byte), which
+        // in turn sorts before "aa" (3 encoded bytes) even though "aa"
+        // would come before "b" in plain alphabetic order.
+        let pairs = [
+            ("b".to_string(), "B".to_string()),
+            ("aa".to_string(), "AA".to_string()),
+            ("a".to_string(), "A".to_string()),
+        ];
+        let bytes = serialize_map_canonical(pairs.iter().map(|(k, v)| (k, v)), Serializer::new_vec())
+            .unwrap()
+            .finalize();
+        assert_eq!(bytes, vec![
+            0xa3,
+            0x61, 0x61, 0x61, 0x41, // "a": "A"
+            0x61, 0x62, 0x61, 0x42, // "b": "B"
+            0x62, 0x61, 0x61, 0x62, 0x41, 0x41, // "aa": "AA"
+        ]);
+    }
+}
@@ -0,0 +1,23 @@
+use internal::core;
+
+/// The CBOR major types, as defined in
+/// [RFC 7049 Section 2.1](https://tools.ietf.org/html/rfc7049#section-2.1).
+///
+/// This is mostly used to report useful error messages of the kind
+/// "expected type X but received type Y".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    UnsignedInteger,
+    NegativeInteger,
+    Bytes,
+    Text,
+    Array,
+    Map,
+    Tag,
+    Special,
+}
+impl core::fmt::Display for Type {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
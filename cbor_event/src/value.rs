@@ -0,0 +1,173 @@
+//! A dynamically typed CBOR value, for when the shape of the data isn't
+//! known ahead of time (debugging, generic tooling, ...).
+
+use std::boxed::Box;
+use std::collections::BTreeMap;
+use std::string::String;
+use std::vec::Vec;
+
+use de::{Deserialize, RawCbor};
+use se::{Serialize, Serializer};
+use len::Len;
+use types::Type;
+use error::Error;
+use result::Result;
+
+/// a restricted subset of [`Value`] that is valid as a CBOR map key.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ObjectKey {
+    Integer(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+}
+impl Deserialize for ObjectKey {
+    fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self> {
+        match raw.cbor_type()? {
+            Type::UnsignedInteger => Ok(ObjectKey::Integer(raw.unsigned_integer()?)),
+            Type::Bytes           => Ok(ObjectKey::Bytes(raw.bytes()?.as_ref().to_vec())),
+            Type::Text            => Ok(ObjectKey::Text(raw.text()?.as_ref().to_string())),
+            t                     => Err(Error::UnsupportedKeyType(t)),
+        }
+    }
+}
+impl Serialize for ObjectKey {
+    fn serialize(&self, serializer: Serializer) -> Result<Serializer> {
+        match self {
+            ObjectKey::Integer(v) => serializer.write_unsigned_integer(*v),
+            ObjectKey::Bytes(v) => serializer.write_bytes(v),
+            ObjectKey::Text(v) => serializer.write_text(v),
+        }
+    }
+}
+
+/// a fully decoded, dynamically typed, CBOR value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    U64(u64),
+    I64(i64),
+    /// a floating point special value (major type 7, additional info
+    /// 25/26/27: half, single or double precision), widened to `f64`.
+    F64(f64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<ObjectKey, Value>),
+    Bool(bool),
+    Null,
+    /// an item wrapped in a CBOR tag (major type 6), preserved across a
+    /// decode -> encode round trip instead of being discarded.
+    Tagged(u64, Box<Value>),
+}
+impl Deserialize for Value {
+    fn deserialize<'a>(raw: &mut RawCbor<'a>) -> Result<Self> {
+        match raw.cbor_type()? {
+            Type::UnsignedInteger => Ok(Value::U64(raw.unsigned_integer()?)),
+            Type::NegativeInteger => Ok(Value::I64(raw.negative_integer()?)),
+            Type::Bytes           => Ok(Value::Bytes(raw.bytes()?.as_ref().to_vec())),
+            Type::Text            => Ok(Value::Text(raw.text()?.as_ref().to_string())),
+            Type::Array           => {
+                let mut vec = Vec::new();
+                match raw.array()? {
+                    Len::Len(len) => {
+                        for _ in 0..len { vec.push(Deserialize::deserialize(raw)?); }
+                    }
+                    Len::Indefinite => {
+                        while !raw.is_break()? { vec.push(Deserialize::deserialize(raw)?); }
+                        raw.special_break()?;
+                    }
+                }
+                raw.leave_container();
+                Ok(Value::Array(vec))
+            }
+            Type::Map => {
+                let mut map = BTreeMap::new();
+                match raw.map()? {
+                    Len::Len(len) => {
+                        for _ in 0..len {
+                            let k = Deserialize::deserialize(raw)?;
+                            let v = Deserialize::deserialize(raw)?;
+                            map.insert(k, v);
+                        }
+                    }
+                    Len::Indefinite => {
+                        while !raw.is_break()? {
+                            let k = Deserialize::deserialize(raw)?;
+                            let v = Deserialize::deserialize(raw)?;
+                            map.insert(k, v);
+                        }
+                        raw.special_break()?;
+                    }
+                }
+                raw.leave_container();
+                Ok(Value::Object(map))
+            }
+            Type::Tag => {
+                let tag = raw.tag()?;
+                let value: Result<Value> = Deserialize::deserialize(raw);
+                raw.leave_container();
+                Ok(Value::Tagged(tag, Box::new(value?)))
+            }
+            Type::Special => {
+                match raw.peek_special_info()? {
+                    20 | 21     => Ok(Value::Bool(raw.bool()?)),
+                    22          => { raw.null()?; Ok(Value::Null) }
+                    23          => { raw.undefined()?; Ok(Value::Null) }
+                    25..=27     => Ok(Value::F64(raw.float()?)),
+                    _           => Err(Error::ExpectedBool),
+                }
+            }
+        }
+    }
+}
+impl Serialize for Value {
+    fn serialize(&self, serializer: Serializer) -> Result<Serializer> {
+        match self {
+            Value::U64(v)   => serializer.write_unsigned_integer(*v),
+            Value::I64(v)   => serializer.write_negative_integer(*v),
+            Value::F64(v)   => serializer.write_f64(*v),
+            Value::Bytes(v) => serializer.write_bytes(v),
+            Value::Text(v)  => serializer.write_text(v),
+            Value::Array(v) => {
+                let mut serializer = serializer.write_array(Len::Len(v.len() as u64))?;
+                for value in v.iter() { serializer = value.serialize(serializer)?; }
+                Ok(serializer)
+            }
+            Value::Object(v) => {
+                let mut serializer = serializer.write_map(Len::Len(v.len() as u64))?;
+                for (k, value) in v.iter() {
+                    serializer = k.serialize(serializer)?;
+                    serializer = value.serialize(serializer)?;
+                }
+                Ok(serializer)
+            }
+            Value::Bool(b) => serializer.write_bool(*b),
+            Value::Null    => serializer.write_null(),
+            Value::Tagged(tag, v) => v.serialize(serializer.write_tag(*tag)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_encode_decode;
+
+    #[test]
+    fn tagged_value_round_trips_through_encode_decode() {
+        let value = Value::Tagged(24, Box::new(Value::U64(7)));
+        assert!(test_encode_decode(&value).unwrap());
+    }
+
+    #[test]
+    fn float_value_round_trips_through_encode_decode() {
+        assert!(test_encode_decode(&Value::F64(1.5)).unwrap());
+    }
+
+    #[test]
+    fn tag_is_preserved_through_deserialize() {
+        let bytes = Serializer::new_vec().write_tag(9).unwrap().write_bool(true).unwrap().finalize();
+        let mut raw = RawCbor::from(&bytes[..]);
+        let value: Value = Deserialize::deserialize(&mut raw).unwrap();
+        assert_eq!(value, Value::Tagged(9, Box::new(Value::Bool(true))));
+    }
+}